@@ -1,53 +1,302 @@
+use std::borrow::{Borrow, BorrowMut};
 use std::fmt;
-use std::io::{self, Read, Write};
+use std::io::{self};
+use std::mem::MaybeUninit;
 use std::pin::Pin;
 use std::task::{Poll, Context};
 
 use super::codec::{Decoder, Encoder};
-use bytes::BytesMut;
-use futures::{Sink, Stream};
-use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, AsyncReadExt};
+use bytes::{BufMut, BytesMut};
+use futures::{ready, Sink, Stream};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use std::fmt::Debug;
 
-/// A unified `Stream` and `Sink` interface to an underlying I/O object, using
-/// the `Encoder` and `Decoder` traits to encode and decode frames.
+const INITIAL_CAPACITY: usize = 8 * 1024;
+
+/// Reads from `io` into `buf`, returning the number of bytes read.
 ///
+/// This mirrors `AsyncReadExt::read_buf` but in polling form so it can be
+/// driven from `poll_next` without allocating a future.
+fn poll_read_buf<S: AsyncRead + Unpin>(
+    io: &mut S,
+    cx: &mut Context<'_>,
+    buf: &mut BytesMut,
+) -> Poll<io::Result<usize>> {
+    let n = {
+        let dst = buf.chunk_mut();
+        // Safety: `ReadBuf` only exposes the initialized portion of `dst`, and
+        // `advance_mut` below is called with exactly the number of bytes the
+        // reader reports as filled.
+        let dst = unsafe { &mut *(dst as *mut _ as *mut [MaybeUninit<u8>]) };
+        let mut read = ReadBuf::uninit(dst);
+        ready!(Pin::new(io).poll_read(cx, &mut read))?;
+        read.filled().len()
+    };
+
+    // Safety: This is guaranteed to be the number of initialized (and read)
+    // bytes due to the invariants provided by `ReadBuf::filled`.
+    unsafe {
+        buf.advance_mut(n);
+    }
 
-const INITIAL_CAPACITY: usize = 8 * 1024;
-const BACKPRESSURE_BOUNDARY: usize = INITIAL_CAPACITY;
+    Poll::Ready(Ok(n))
+}
 
-pub struct Framed<S, C> {
-    /// The inner transport used to read bytes to and write bytes to
-    pub io: S,
+/// The read half of the framing state: the buffer of bytes read but not yet
+/// decoded, plus the flags that drive the `Stream` loop.
+pub(crate) struct ReadFrame {
+    pub(crate) eof: bool,
+    pub(crate) is_readable: bool,
+    pub(crate) buffer: BytesMut,
+    /// Set once the decoder has returned an error. The buffer may be corrupt
+    /// at this point, so the stream must not touch the codec again.
+    pub(crate) has_errored: bool,
+}
 
-    /// The codec
-    pub codec: C,
+/// The write half of the framing state: the buffer of encoded-but-unwritten
+/// bytes, plus the threshold at which the `Sink` starts applying backpressure.
+pub(crate) struct WriteFrame {
+    pub(crate) buffer: BytesMut,
+    pub(crate) backpressure_boundary: usize,
+}
 
-    /// The buffer with read but unprocessed data.
-    pub read_buf: BytesMut,
+/// Both halves of the framing state, used by the duplex [`Framed`].
+#[derive(Default)]
+pub(crate) struct RWFrames {
+    pub(crate) read: ReadFrame,
+    pub(crate) write: WriteFrame,
+}
 
-    pub(crate) eof: bool,
+impl Default for ReadFrame {
+    fn default() -> Self {
+        ReadFrame {
+            eof: false,
+            is_readable: false,
+            buffer: BytesMut::with_capacity(INITIAL_CAPACITY),
+            has_errored: false,
+        }
+    }
+}
 
-    pub(crate) is_readable: bool,
+impl Default for WriteFrame {
+    fn default() -> Self {
+        WriteFrame {
+            buffer: BytesMut::with_capacity(INITIAL_CAPACITY),
+            backpressure_boundary: INITIAL_CAPACITY,
+        }
+    }
+}
 
-    /// A buffer with unprocessed data which are not written yet.
-    pub write_buf: BytesMut,
+impl Borrow<ReadFrame> for RWFrames {
+    fn borrow(&self) -> &ReadFrame {
+        &self.read
+    }
+}
+
+impl BorrowMut<ReadFrame> for RWFrames {
+    fn borrow_mut(&mut self) -> &mut ReadFrame {
+        &mut self.read
+    }
+}
+
+impl Borrow<WriteFrame> for RWFrames {
+    fn borrow(&self) -> &WriteFrame {
+        &self.write
+    }
+}
+
+impl BorrowMut<WriteFrame> for RWFrames {
+    fn borrow_mut(&mut self) -> &mut WriteFrame {
+        &mut self.write
+    }
+}
+
+/// The shared implementation behind [`Framed`], [`FramedRead`] and
+/// [`FramedWrite`].
+///
+/// `State` selects which buffers are present: `ReadFrame` for read-only,
+/// `WriteFrame` for write-only, and `RWFrames` for the duplex case. `Stream`
+/// is implemented for any state that can borrow a `ReadFrame` and `Sink` for
+/// any state that can borrow a `WriteFrame`, so all three public types share a
+/// single decode/encode loop.
+pub(crate) struct FramedImpl<S, C, State> {
+    pub(crate) inner: S,
+    pub(crate) codec: C,
+    pub(crate) state: State,
+}
+
+impl<S, C, R> Stream for FramedImpl<S, C, R>
+where
+    S: AsyncRead + Unpin,
+    C: Decoder + Unpin,
+    R: BorrowMut<ReadFrame> + Unpin,
+{
+    type Item = Result<C::Item, C::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let state: &mut ReadFrame = this.state.borrow_mut();
+        loop {
+            // Once the decoder has errored the read buffer may hold a partial,
+            // corrupt frame. Returning `None` here fuses the stream so a
+            // subsequent poll never hands that buffer back to the codec.
+            if state.has_errored {
+                state.is_readable = false;
+                return Poll::Ready(None);
+            }
+
+            // Repeatedly call `decode` or `decode_eof` as long as it is
+            // "readable". Readable is defined as not having returned `None`. If
+            // the upstream has returned EOF, and the decoder is no longer
+            // readable, it can be assumed that the decoder will never become
+            // readable again, at which point the stream is terminated.
+            if state.is_readable {
+                if state.eof {
+                    // Drain any trailing frames from the buffer one at a time,
+                    // terminating the stream once `decode_eof` yields `None`.
+                    match this.codec.decode_eof(&mut state.buffer) {
+                        Ok(Some(frame)) => return Poll::Ready(Some(Ok(frame))),
+                        Ok(None) => return Poll::Ready(None),
+                        Err(e) => {
+                            state.has_errored = true;
+                            return Poll::Ready(Some(Err(e)));
+                        }
+                    }
+                }
+
+                match this.codec.decode(&mut state.buffer) {
+                    Ok(Some(frame)) => return Poll::Ready(Some(Ok(frame))),
+                    Ok(None) => {}
+                    Err(e) => {
+                        state.has_errored = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+
+                state.is_readable = false;
+            }
+
+            assert!(!state.eof);
+
+            // Otherwise, try to read more data and try again. Make sure we've
+            // got room for at least one byte to read to ensure that we don't
+            // get a spurious 0 that looks like EOF.
+            state.buffer.reserve(1);
+            if 0 == ready!(poll_read_buf(&mut this.inner, cx, &mut state.buffer))? {
+                state.eof = true;
+            }
+
+            state.is_readable = true;
+        }
+    }
+}
+
+impl<S, C, W> Sink<C::Item> for FramedImpl<S, C, W>
+where
+    S: AsyncWrite + Unpin,
+    C: Encoder + Unpin,
+    W: BorrowMut<WriteFrame> + Unpin,
+{
+    type Error = C::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // `poll_ready` only needs to guarantee that there is room below the
+        // backpressure boundary for another frame; draining all the way to the
+        // socket is `poll_flush`'s job. Once the buffer reaches the boundary we
+        // flush to make room, otherwise accept the frame immediately.
+        let (len, boundary) = {
+            let state: &WriteFrame = self.state.borrow();
+            (state.buffer.len(), state.backpressure_boundary)
+        };
+        if len >= boundary {
+            return self.poll_flush(cx);
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: C::Item) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        let state: &mut WriteFrame = this.state.borrow_mut();
+        this.codec.encode(item, &mut state.buffer)?;
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        let state: &mut WriteFrame = this.state.borrow_mut();
+
+        while !state.buffer.is_empty() {
+            let n = ready!(Pin::new(&mut this.inner).poll_write(cx, &state.buffer))?;
+
+            if n == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write frame to transport",
+                )
+                .into()));
+            }
+
+            let _ = state.buffer.split_to(n);
+        }
+
+        // Try flushing the underlying IO
+        ready!(Pin::new(&mut this.inner).poll_flush(cx))?;
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Make sure every queued frame has reached the socket before shutting
+        // the write half down.
+        ready!(self.as_mut().poll_flush(cx))?;
+        ready!(Pin::new(&mut self.get_mut().inner).poll_shutdown(cx))?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A unified `Stream` and `Sink` interface to an underlying I/O object, using
+/// the `Encoder` and `Decoder` traits to encode and decode frames.
+pub struct Framed<S, C> {
+    inner: FramedImpl<S, C, RWFrames>,
 }
 
 impl<S, C> Framed<S, C> {
     pub fn new(io: S, codec: C) -> Self {
         Framed {
-            io,
-            codec,
-            read_buf: BytesMut::with_capacity(INITIAL_CAPACITY),
-            eof: false,
-            is_readable: false,
-            write_buf: BytesMut::with_capacity(INITIAL_CAPACITY),
+            inner: FramedImpl {
+                inner: io,
+                codec,
+                state: RWFrames::default(),
+            },
         }
     }
 
+    /// Creates a new `Framed` with the given backpressure boundary.
+    ///
+    /// Equivalent to `new` followed by `set_backpressure_boundary`.
+    pub fn with_backpressure_boundary(io: S, codec: C, boundary: usize) -> Self {
+        let mut framed = Framed::new(io, codec);
+        framed.set_backpressure_boundary(boundary);
+        framed
+    }
+
+    /// Returns the number of bytes the write buffer may hold before the `Sink`
+    /// begins applying backpressure.
+    pub fn backpressure_boundary(&self) -> usize {
+        self.inner.state.write.backpressure_boundary
+    }
+
+    /// Sets the number of bytes the write buffer may hold before the `Sink`
+    /// begins applying backpressure.
+    ///
+    /// Raise this for bulk writes such as large market-data snapshots, or lower
+    /// it to bound memory when writing to a slow TWS gateway.
+    pub fn set_backpressure_boundary(&mut self, boundary: usize) {
+        self.inner.state.write.backpressure_boundary = boundary;
+    }
+
     pub fn get_ref(&self) -> &S {
-        &self.io
+        &self.inner.inner
     }
 
     /// Returns a mutable reference to the underlying I/O stream wrapped by
@@ -57,17 +306,17 @@ impl<S, C> Framed<S, C> {
     /// of data coming in as it may corrupt the stream of frames otherwise
     /// being worked with.
     pub fn get_mut(&mut self) -> &mut S {
-        &mut self.io
+        &mut self.inner.inner
     }
 
     /// Returns a reference to the underlying codec.
     pub fn get_codec(&self) -> &C {
-        &self.codec
+        &self.inner.codec
     }
 
     /// Returns a mutable reference to the underlying codec.
     pub fn get_codec_mut(&mut self) -> &mut C {
-        &mut self.codec
+        &mut self.inner.codec
     }
 
     /// Consumes the `Frame`, returning its underlying I/O stream.
@@ -76,143 +325,319 @@ impl<S, C> Framed<S, C> {
     /// of data coming in as it may corrupt the stream of frames otherwise
     /// being worked with.
     pub fn into_inner(self) -> S {
-        self.io
+        self.inner.inner
+    }
+
+    /// Consumes the `Framed`, returning its parts: the underlying I/O stream,
+    /// the codec, and both buffers.
+    ///
+    /// Together with [`from_parts`](Framed::from_parts) this lets a connection
+    /// be handed off between layers without losing buffered bytes.
+    pub fn into_parts(self) -> FramedParts<S, C> {
+        FramedParts {
+            io: self.inner.inner,
+            codec: self.inner.codec,
+            read_buf: self.inner.state.read.buffer,
+            write_buf: self.inner.state.write.buffer,
+            _priv: (),
+        }
+    }
+
+    /// Reconstructs a `Framed` from its [`FramedParts`].
+    ///
+    /// If `read_buf` already holds bytes, the stream is marked readable so the
+    /// next `poll_next` decodes them instead of waiting on the wire. This is
+    /// how bytes pulled off the socket during TWS API version negotiation are
+    /// preserved when the raw socket is upgraded to a framed transport.
+    pub fn from_parts(parts: FramedParts<S, C>) -> Framed<S, C> {
+        let FramedParts {
+            io,
+            codec,
+            mut read_buf,
+            write_buf,
+            ..
+        } = parts;
+
+        let is_readable = !read_buf.is_empty();
+
+        // Ensure the read buffer has a sane amount of headroom even when the
+        // handshake layer handed us a tightly-sized buffer.
+        let capacity = read_buf.capacity();
+        if capacity < INITIAL_CAPACITY {
+            read_buf.reserve(INITIAL_CAPACITY - capacity);
+        }
+
+        Framed {
+            inner: FramedImpl {
+                inner: io,
+                codec,
+                state: RWFrames {
+                    read: ReadFrame {
+                        eof: false,
+                        is_readable,
+                        buffer: read_buf,
+                        has_errored: false,
+                    },
+                    write: WriteFrame {
+                        buffer: write_buf,
+                        backpressure_boundary: INITIAL_CAPACITY,
+                    },
+                },
+            },
+        }
+    }
+}
+
+/// The component parts of a [`Framed`], as produced by
+/// [`Framed::into_parts`] and consumed by [`Framed::from_parts`].
+pub struct FramedParts<S, C> {
+    /// The inner transport used to read and write bytes.
+    pub io: S,
+
+    /// The codec.
+    pub codec: C,
+
+    /// The buffer with read but unprocessed data.
+    pub read_buf: BytesMut,
+
+    /// The buffer with encoded but unwritten data.
+    pub write_buf: BytesMut,
+
+    /// Keeps the struct non-exhaustive so fields can be added without a
+    /// breaking change; construct via [`FramedParts::new`].
+    _priv: (),
+}
+
+impl<S, C> FramedParts<S, C> {
+    /// Creates a new `FramedParts` with empty buffers.
+    pub fn new(io: S, codec: C) -> FramedParts<S, C> {
+        FramedParts {
+            io,
+            codec,
+            read_buf: BytesMut::new(),
+            write_buf: BytesMut::new(),
+            _priv: (),
+        }
     }
 }
 
 impl<S: Debug, C: Debug> fmt::Debug for Framed<S, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Transport")
-            .field("inner", &self.io)
-            .field("codec", &self.codec)
-            .field("read_buf", &self.read_buf)
-            .field("eof", &self.eof)
-            .field("is_readable", &self.is_readable)
-            .field("write_buf", &self.write_buf)
+            .field("inner", &self.inner.inner)
+            .field("codec", &self.inner.codec)
+            .field("read_buf", &self.inner.state.read.buffer)
+            .field("eof", &self.inner.state.read.eof)
+            .field("is_readable", &self.inner.state.read.is_readable)
+            .field("write_buf", &self.inner.state.write.buffer)
             .finish()
     }
 }
 
-impl<S: AsyncRead, C: Decoder> Stream for Framed<S, C> {
+impl<S: AsyncRead + Unpin, C: Decoder + Unpin> Stream for Framed<S, C> {
     type Item = Result<C::Item, C::Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        loop {
-            // Repeatedly call `decode` or `decode_eof` as long as it is
-            // "readable". Readable is defined as not having returned `None`. If
-            // the upstream has returned EOF, and the decoder is no longer
-            // readable, it can be assumed that the decoder will never become
-            // readable again, at which point the stream is terminated.
-            if self.is_readable {
-                if self.eof {
-                    let frame = self.codec.decode_eof(&mut self.read_buf);
-                    return Poll::Ready(Some(frame.map(|f| f.unwrap())));
-                }
+        Pin::new(&mut self.get_mut().inner).poll_next(cx)
+    }
+}
 
-                if let Some(frame) = self.codec.decode(&mut self.read_buf)? {
-                    return Poll::Ready(Some(Ok(frame)));
-                }
+impl<S: AsyncWrite + Unpin, C: Encoder + Unpin> Sink<C::Item> for Framed<S, C> {
+    type Error = C::Error;
 
-                self.is_readable = false;
-            }
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_ready(cx)
+    }
 
-            assert!(!self.eof);
+    fn start_send(self: Pin<&mut Self>, item: C::Item) -> Result<(), Self::Error> {
+        Pin::new(&mut self.get_mut().inner).start_send(item)
+    }
 
-            // Otherwise, try to read more data and try again. Make sure we've
-            // got room for at least one byte to read to ensure that we don't
-            // get a spurious 0 that looks like EOF
-            self.read_buf.reserve(1);
-            if 0 == ready!(self.io.read_buf(&mut self.read_buf)) {
-                self.eof = true;
-            }
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
 
-            self.is_readable = true;
-        }
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
     }
 }
 
-impl<S: AsyncWrite, C: Encoder> Sink<C::Item> for Framed<S, C> {
-    type Error = C::Error;
-
-    fn start_send(self: Pin<&mut Self>, item: C::Item) -> Result<(), Self::Error> {
-        // If the buffer is already over 8KiB, then attempt to flush it. If after flushing it's
-        // *still* over 8KiB, then apply backpressure (reject the send).
-        if self.write_buf.len() >= BACKPRESSURE_BOUNDARY {
-            self.poll_ready()?;
+/// A `Stream` over the frames decoded from an underlying read half, using a
+/// `Decoder`.
+///
+/// Unlike [`Framed`], this holds only the read-side state and implements only
+/// `Stream`, which needs the inner `S` to be `AsyncRead`, so it can be driven
+/// from a task that owns only the read half of a split socket.
+pub struct FramedRead<S, D> {
+    inner: FramedImpl<S, D, ReadFrame>,
+}
 
-            if self.write_buf.len() >= BACKPRESSURE_BOUNDARY {
-                return Ok(Poll::Pending(item));
-            }
+impl<S, D> FramedRead<S, D> {
+    pub fn new(io: S, codec: D) -> Self {
+        FramedRead {
+            inner: FramedImpl {
+                inner: io,
+                codec,
+                state: ReadFrame::default(),
+            },
         }
+    }
 
-        self.codec.encode(item, &mut self.write_buf)?;
+    pub fn get_ref(&self) -> &S {
+        &self.inner.inner
+    }
 
-        Ok(Poll::Ready(()))
+    /// Returns a mutable reference to the underlying I/O stream wrapped by
+    /// `FramedRead`.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data coming in as it may corrupt the stream of frames otherwise
+    /// being worked with.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner.inner
     }
 
-    /* fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        // try_ready!(self.poll_complete());
-        self.io.shutdown()
-    }*/
+    /// Returns a reference to the underlying decoder.
+    pub fn get_codec(&self) -> &D {
+        &self.inner.codec
+    }
 
-    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        //trace!("flushing framed transport");
+    /// Returns a mutable reference to the underlying decoder.
+    pub fn get_codec_mut(&mut self) -> &mut D {
+        &mut self.inner.codec
+    }
 
-        while !self.write_buf.is_empty() {
-            //trace!("writing; remaining={}", self.buffer.len());
+    /// Consumes the `FramedRead`, returning its underlying I/O stream.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data coming in as it may corrupt the stream of frames otherwise
+    /// being worked with.
+    pub fn into_inner(self) -> S {
+        self.inner.inner
+    }
+}
 
-            let n = ready!(self.io.poll_write(&self.write_buf));
+impl<S: Debug, D: Debug> fmt::Debug for FramedRead<S, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FramedRead")
+            .field("inner", &self.inner.inner)
+            .field("codec", &self.inner.codec)
+            .field("read_buf", &self.inner.state.buffer)
+            .field("eof", &self.inner.state.eof)
+            .field("is_readable", &self.inner.state.is_readable)
+            .finish()
+    }
+}
 
-            if n == 0 {
-                return Poll::Ready(Err(io::Error::new(
-                    io::ErrorKind::WriteZero,
-                    "failed to
-                                          write frame to transport",
-                ).into()))
-            }
+impl<S: AsyncRead + Unpin, D: Decoder + Unpin> Stream for FramedRead<S, D> {
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().inner).poll_next(cx)
+    }
+}
 
-            // TODO: Add a way to `bytes` to do this w/o returning the drained
-            // data.
-            let _ = self.write_buf.split_to(n);
+/// A `Sink` for frames encoded onto an underlying write half, using an
+/// `Encoder`.
+///
+/// Unlike [`Framed`], this holds only the write-side state and implements only
+/// `Sink`, which needs the inner `S` to be `AsyncWrite`, so it can be driven
+/// from a task that owns only the write half of a split socket.
+pub struct FramedWrite<S, E> {
+    inner: FramedImpl<S, E, WriteFrame>,
+}
+
+impl<S, E> FramedWrite<S, E> {
+    pub fn new(io: S, codec: E) -> Self {
+        FramedWrite {
+            inner: FramedImpl {
+                inner: io,
+                codec,
+                state: WriteFrame::default(),
+            },
         }
+    }
 
-        // Try flushing the underlying IO
-        ready!(self.io.flush());
+    /// Creates a new `FramedWrite` with the given backpressure boundary.
+    ///
+    /// Equivalent to `new` followed by `set_backpressure_boundary`.
+    pub fn with_backpressure_boundary(io: S, codec: E, boundary: usize) -> Self {
+        let mut framed = FramedWrite::new(io, codec);
+        framed.set_backpressure_boundary(boundary);
+        framed
+    }
 
-        //trace!("framed transport flushed");
-        Poll::Ready(Ok(()))
+    /// Returns the number of bytes the write buffer may hold before the `Sink`
+    /// begins applying backpressure.
+    pub fn backpressure_boundary(&self) -> usize {
+        self.inner.state.backpressure_boundary
+    }
 
+    /// Sets the number of bytes the write buffer may hold before the `Sink`
+    /// begins applying backpressure.
+    ///
+    /// Raise this for bulk writes such as large market-data snapshots, or lower
+    /// it to bound memory when writing to a slow TWS gateway.
+    pub fn set_backpressure_boundary(&mut self, boundary: usize) {
+        self.inner.state.backpressure_boundary = boundary;
     }
 
-    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        todo!()
+    pub fn get_ref(&self) -> &S {
+        &self.inner.inner
+    }
+
+    /// Returns a mutable reference to the underlying I/O stream wrapped by
+    /// `FramedWrite`.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data being written out as it may corrupt the stream of frames
+    /// otherwise being worked with.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner.inner
     }
-}
 
-impl<S: Read, C> Read for Framed<S, C> {
-    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
-        self.io.read(dst)
+    /// Returns a reference to the underlying encoder.
+    pub fn get_codec(&self) -> &E {
+        &self.inner.codec
+    }
+
+    /// Returns a mutable reference to the underlying encoder.
+    pub fn get_codec_mut(&mut self) -> &mut E {
+        &mut self.inner.codec
+    }
+
+    /// Consumes the `FramedWrite`, returning its underlying I/O stream.
+    pub fn into_inner(self) -> S {
+        self.inner.inner
     }
 }
 
-impl<S: AsyncRead, C> AsyncRead for Framed<S, C> {
-    /* unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
-        self.io.prepare_uninitialized_buffer(buf)
-    } */
+impl<S: Debug, E: Debug> fmt::Debug for FramedWrite<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FramedWrite")
+            .field("inner", &self.inner.inner)
+            .field("codec", &self.inner.codec)
+            .field("write_buf", &self.inner.state.buffer)
+            .finish()
+    }
 }
 
-impl<S: Write, C> Write for Framed<S, C> {
-    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
-        self.io.write(src)
+impl<S: AsyncWrite + Unpin, E: Encoder + Unpin> Sink<E::Item> for FramedWrite<S, E> {
+    type Error = E::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_ready(cx)
     }
 
-    fn flush(&mut self) -> io::Result<()> {
-        self.io.flush()
+    fn start_send(self: Pin<&mut Self>, item: E::Item) -> Result<(), Self::Error> {
+        Pin::new(&mut self.get_mut().inner).start_send(item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
     }
-}
 
-impl<S: AsyncWrite, C> AsyncWrite for Framed<S, C> {
-    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
-        self.io.shutdown()
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
     }
 }